@@ -51,6 +51,21 @@ pub enum BootError {
     BadMagic,
     /// The header version present is not supported.
     UnknownVersion,
+    /// The bootconfig trailer magic, size, or checksum did not match.
+    BadBootconfig,
+    /// The boot signature region is missing (`signature_size` is zero) or
+    /// overruns the buffer.
+    BadSignatureRegion,
+    /// The vendor ramdisk table entry size/count/offsets are inconsistent
+    /// with `vendor_ramdisk_table_size` or the vendor ramdisk section.
+    BadRamdiskTable,
+    /// No vendor ramdisk table entry matched the requested selector.
+    RamdiskNotFound,
+    /// No `AvbFooter` was found, or the vbmeta/hash descriptor it points to
+    /// is malformed.
+    BadAvbFooter,
+    /// An AVB hash descriptor's digest did not match the computed hash.
+    VerificationFailed,
     /// Catch-all for remaining errors.
     UnknownError,
 }
@@ -72,6 +87,15 @@ impl VendorRamdiskType {
     pub const DLKM: Self = Self(3);
 }
 
+/// Indicates the value is unspecified.
+pub const VENDOR_RAMDISK_TYPE_NONE: VendorRamdiskType = VendorRamdiskType::NONE;
+/// Ramdisk contains platform specific bits, so the bootloader should always load these into memory.
+pub const VENDOR_RAMDISK_TYPE_PLATFORM: VendorRamdiskType = VendorRamdiskType::PLATFORM;
+/// Ramdisk contains recovery resources, so the bootloader should load these when booting into recovery.
+pub const VENDOR_RAMDISK_TYPE_RECOVERY: VendorRamdiskType = VendorRamdiskType::RECOVERY;
+/// Ramdisk contains dynamic loadable kernel modules.
+pub const VENDOR_RAMDISK_TYPE_DLKM: VendorRamdiskType = VendorRamdiskType::DLKM;
+
 type Major = u8;
 type Minor = u8;
 type Patch = u8;
@@ -421,7 +445,104 @@ pub struct VendorRamdiskTableEntryV4 {
     pub board_id: [u32; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
 }
 
-// TODO(dovs): implement an iterator over ramdisk table entries
+/// Rounds `value` up to the next multiple of `page_size`.
+fn round_up_to_page_size(value: usize, page_size: usize) -> usize {
+    if page_size == 0 {
+        return value;
+    }
+    value.div_ceil(page_size) * page_size
+}
+
+impl VendorBootHdrV4 {
+    /// Computes the byte offset of the vendor ramdisk table, i.e. the
+    /// page-aligned offset immediately following the header, vendor ramdisk,
+    /// and dtb sections.
+    fn vendor_ramdisk_table_offset(&self) -> usize {
+        let page_size = self.v3_img_hdr.page_size as usize;
+        let header_size = round_up_to_page_size(self.v3_img_hdr.header_size as usize, page_size);
+        let ramdisk_size =
+            round_up_to_page_size(self.v3_img_hdr.vendor_ramdisk_size as usize, page_size);
+        let dtb_size = round_up_to_page_size(self.v3_img_hdr.dtb_size as usize, page_size);
+        header_size + ramdisk_size + dtb_size
+    }
+
+    /// Given the full backing buffer the header was parsed from, returns an
+    /// iterator over the vendor ramdisk table entries. Callers can inspect
+    /// each entry's `ramdisk_type`/`board_id` to select the ramdisks to load.
+    ///
+    /// Validates that `vendor_ramdisk_table_entry_size` is large enough to
+    /// hold a `VendorRamdiskTableEntryV4`, that the table fits within
+    /// `vendor_ramdisk_table_size`, and that every entry's
+    /// `ramdisk_offset`/`ramdisk_size` stays within the vendor ramdisk
+    /// section, returning `BootError::BadRamdiskTable` otherwise.
+    fn ramdisk_table_entries<'a>(
+        &self,
+        buffer: &'a [u8],
+    ) -> BootResult<impl Iterator<Item = LayoutVerified<&'a [u8], VendorRamdiskTableEntryV4>>> {
+        let entry_num = self.vendor_ramdisk_table_entry_num as usize;
+        let entry_size = self.vendor_ramdisk_table_entry_size as usize;
+        if entry_size < size_of::<VendorRamdiskTableEntryV4>() {
+            return Err(BootError::BadRamdiskTable);
+        }
+        let table_size = entry_num.checked_mul(entry_size).ok_or(BootError::BadRamdiskTable)?;
+        if table_size > self.vendor_ramdisk_table_size as usize {
+            return Err(BootError::BadRamdiskTable);
+        }
+
+        let offset = self.vendor_ramdisk_table_offset();
+        let table_end = offset.checked_add(table_size).ok_or(BootError::BufferTooSmall)?;
+        if table_end > buffer.len() {
+            return Err(BootError::BufferTooSmall);
+        }
+        let table = &buffer[offset..table_end];
+
+        let vendor_ramdisk_size = self.v3_img_hdr.vendor_ramdisk_size as usize;
+        for i in 0..entry_num {
+            let start = i * entry_size;
+            let entry = LayoutVerified::<&[u8], VendorRamdiskTableEntryV4>::new_from_prefix(
+                &table[start..start + entry_size],
+            )
+            .unwrap()
+            .0;
+            let entry_end = (entry.ramdisk_offset as usize)
+                .checked_add(entry.ramdisk_size as usize)
+                .ok_or(BootError::BadRamdiskTable)?;
+            if entry_end > vendor_ramdisk_size {
+                return Err(BootError::BadRamdiskTable);
+            }
+        }
+
+        Ok((0..entry_num).map(move |i| {
+            let start = i * entry_size;
+            LayoutVerified::<&[u8], VendorRamdiskTableEntryV4>::new_from_prefix(
+                &table[start..start + entry_size],
+            )
+            .unwrap()
+            .0
+        }))
+    }
+}
+
+impl<B: ByteSlice + PartialEq> VendorBootHdr<B> {
+    /// Given the full backing buffer the header was parsed from, returns an
+    /// iterator over the vendor ramdisk table entries of a version 4 vendor
+    /// boot image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BootError::UnknownVersion` if this is a version 3 image,
+    /// which has no ramdisk table, and `BootError::BufferTooSmall` if the
+    /// table does not fit within `buffer`.
+    pub fn ramdisk_table_entries<'a>(
+        &self,
+        buffer: &'a [u8],
+    ) -> BootResult<impl Iterator<Item = LayoutVerified<&'a [u8], VendorRamdiskTableEntryV4>>> {
+        match self {
+            Self::V4Hdr(hdr) => hdr.ramdisk_table_entries(buffer),
+            Self::V3Hdr(_) => Err(BootError::UnknownVersion),
+        }
+    }
+}
 
 #[derive(PartialEq, Debug)]
 /// Generalized boot image from a backing store of bytes.
@@ -507,6 +628,243 @@ impl<B: ByteSlice + PartialEq> BootImg<B> {
     }
 }
 
+impl BootImgHdrV4 {
+    /// Computes the byte offset of the boot signature region, i.e. the
+    /// page-aligned offset immediately following the kernel and ramdisk
+    /// sections.
+    fn signature_offset(&self) -> usize {
+        const PAGE_SIZE: usize = 4096;
+        PAGE_SIZE
+            + round_up_to_page_size(self.v3_hdr.kernel_size as usize, PAGE_SIZE)
+            + round_up_to_page_size(self.v3_hdr.ramdisk_size as usize, PAGE_SIZE)
+    }
+}
+
+impl<B: ByteSlice + PartialEq> BootImg<B> {
+    /// Given the full backing buffer the header was parsed from, returns the
+    /// boot signature (AVB/VBMeta-style) region of a version 4 boot image.
+    ///
+    /// This only locates the region and returns its raw bytes; it does not
+    /// decode them. `parse_avb_hash_descriptor` decodes the hash descriptor
+    /// of a vbmeta image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BootError::UnknownVersion` for any header version other than
+    /// 4, and `BootError::BadSignatureRegion` if `signature_size` is zero or
+    /// the region overruns `buffer`.
+    pub fn boot_signature<'a>(&self, buffer: &'a [u8]) -> BootResult<&'a [u8]> {
+        match self {
+            Self::V4Hdr(hdr) => {
+                let size = hdr.signature_size as usize;
+                if size == 0 {
+                    return Err(BootError::BadSignatureRegion);
+                }
+                let offset = hdr.signature_offset();
+                let end = offset.checked_add(size).ok_or(BootError::BadSignatureRegion)?;
+                if end > buffer.len() {
+                    return Err(BootError::BadSignatureRegion);
+                }
+                Ok(&buffer[offset..end])
+            }
+            _ => Err(BootError::UnknownVersion),
+        }
+    }
+}
+
+/// Page-aligned byte ranges, as `(offset, size)` pairs, of every payload
+/// section in a boot image. A field is `None` if the corresponding section
+/// does not exist for the image's header version.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct BootImgLayout {
+    /// Range of the kernel section.
+    pub kernel: (usize, usize),
+    /// Range of the ramdisk section.
+    pub ramdisk: (usize, usize),
+    /// Range of the second-stage section. Only present for versions 0-2.
+    pub second: Option<(usize, usize)>,
+    /// Range of the recovery DTBO/ACPIO section. Only present for versions 1-2.
+    pub recovery_dtbo: Option<(usize, usize)>,
+    /// Range of the DTB section. Only present for version 2.
+    pub dtb: Option<(usize, usize)>,
+    /// Range of the boot signature (AVB) section. Only present for version 4.
+    pub signature: Option<(usize, usize)>,
+}
+
+impl<B: ByteSlice + PartialEq> BootImg<B> {
+    /// Given the full backing buffer the header was parsed from, computes the
+    /// byte range of every payload section.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BootError::BufferTooSmall` if any computed range exceeds
+    /// `buffer`'s length.
+    pub fn layout(&self, buffer: &[u8]) -> BootResult<BootImgLayout> {
+        let checked_range = |offset: usize, size: usize| -> BootResult<(usize, usize)> {
+            let end = offset.checked_add(size).ok_or(BootError::BufferTooSmall)?;
+            if end > buffer.len() {
+                return Err(BootError::BufferTooSmall);
+            }
+            Ok((offset, size))
+        };
+
+        match self {
+            Self::V0Hdr(hdr) => {
+                let page_size = hdr.page_size as usize;
+                let mut offset = round_up_to_page_size(size_of::<BootImgHdrV0>(), page_size);
+                let kernel = checked_range(offset, hdr.kernel_size as usize)?;
+                offset += round_up_to_page_size(kernel.1, page_size);
+                let ramdisk = checked_range(offset, hdr.ramdisk_size as usize)?;
+                offset += round_up_to_page_size(ramdisk.1, page_size);
+                let second = checked_range(offset, hdr.second_size as usize)?;
+                Ok(BootImgLayout {
+                    kernel,
+                    ramdisk,
+                    second: Some(second),
+                    recovery_dtbo: None,
+                    dtb: None,
+                    signature: None,
+                })
+            }
+            Self::V1Hdr(hdr) => {
+                let page_size = hdr.v0_hdr.page_size as usize;
+                let mut offset = round_up_to_page_size(hdr.header_size as usize, page_size);
+                let kernel = checked_range(offset, hdr.v0_hdr.kernel_size as usize)?;
+                offset += round_up_to_page_size(kernel.1, page_size);
+                let ramdisk = checked_range(offset, hdr.v0_hdr.ramdisk_size as usize)?;
+                offset += round_up_to_page_size(ramdisk.1, page_size);
+                let second = checked_range(offset, hdr.v0_hdr.second_size as usize)?;
+                offset += round_up_to_page_size(second.1, page_size);
+                let recovery_dtbo = checked_range(offset, hdr.recovery_dtbo_size as usize)?;
+                Ok(BootImgLayout {
+                    kernel,
+                    ramdisk,
+                    second: Some(second),
+                    recovery_dtbo: Some(recovery_dtbo),
+                    dtb: None,
+                    signature: None,
+                })
+            }
+            Self::V2Hdr(hdr) => {
+                let page_size = hdr.v1_hdr.v0_hdr.page_size as usize;
+                let mut offset = round_up_to_page_size(hdr.v1_hdr.header_size as usize, page_size);
+                let kernel = checked_range(offset, hdr.v1_hdr.v0_hdr.kernel_size as usize)?;
+                offset += round_up_to_page_size(kernel.1, page_size);
+                let ramdisk = checked_range(offset, hdr.v1_hdr.v0_hdr.ramdisk_size as usize)?;
+                offset += round_up_to_page_size(ramdisk.1, page_size);
+                let second = checked_range(offset, hdr.v1_hdr.v0_hdr.second_size as usize)?;
+                offset += round_up_to_page_size(second.1, page_size);
+                let recovery_dtbo = checked_range(offset, hdr.v1_hdr.recovery_dtbo_size as usize)?;
+                offset += round_up_to_page_size(recovery_dtbo.1, page_size);
+                let dtb = checked_range(offset, hdr.dtb_size as usize)?;
+                Ok(BootImgLayout {
+                    kernel,
+                    ramdisk,
+                    second: Some(second),
+                    recovery_dtbo: Some(recovery_dtbo),
+                    dtb: Some(dtb),
+                    signature: None,
+                })
+            }
+            Self::V3Hdr(hdr) => {
+                const PAGE_SIZE: usize = 4096;
+                let kernel = checked_range(PAGE_SIZE, hdr.kernel_size as usize)?;
+                let ramdisk_offset = PAGE_SIZE + round_up_to_page_size(kernel.1, PAGE_SIZE);
+                let ramdisk = checked_range(ramdisk_offset, hdr.ramdisk_size as usize)?;
+                Ok(BootImgLayout {
+                    kernel,
+                    ramdisk,
+                    second: None,
+                    recovery_dtbo: None,
+                    dtb: None,
+                    signature: None,
+                })
+            }
+            Self::V4Hdr(hdr) => {
+                const PAGE_SIZE: usize = 4096;
+                let kernel = checked_range(PAGE_SIZE, hdr.v3_hdr.kernel_size as usize)?;
+                let ramdisk_offset = PAGE_SIZE + round_up_to_page_size(kernel.1, PAGE_SIZE);
+                let ramdisk = checked_range(ramdisk_offset, hdr.v3_hdr.ramdisk_size as usize)?;
+                let signature_offset = hdr.signature_offset();
+                let signature = checked_range(signature_offset, hdr.signature_size as usize)?;
+                Ok(BootImgLayout {
+                    kernel,
+                    ramdisk,
+                    second: None,
+                    recovery_dtbo: None,
+                    dtb: None,
+                    signature: Some(signature),
+                })
+            }
+        }
+    }
+}
+
+/// Page-aligned byte ranges, as `(offset, size)` pairs, of every payload
+/// section in a version 3 or 4 vendor boot image. A field is `None` if the
+/// corresponding section does not exist for the image's header version; an
+/// empty section is reported with size 0.
+#[derive(Debug, PartialEq, Eq, Clone, Copy, Default)]
+pub struct VendorBootLayout {
+    /// Range of the vendor ramdisk section.
+    pub vendor_ramdisk: (usize, usize),
+    /// Range of the DTB section.
+    pub dtb: (usize, usize),
+    /// Range of the vendor ramdisk table. Only present for version 4.
+    pub ramdisk_table: Option<(usize, usize)>,
+    /// Range of the bootconfig section. Only present for version 4.
+    pub bootconfig: Option<(usize, usize)>,
+}
+
+impl<B: ByteSlice + PartialEq> VendorBootHdr<B> {
+    /// Given the full backing buffer the header was parsed from, computes the
+    /// byte range of every payload section.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BootError::BufferTooSmall` if any computed range exceeds
+    /// `buffer`'s length.
+    pub fn layout(&self, buffer: &[u8]) -> BootResult<VendorBootLayout> {
+        let checked_range = |offset: usize, size: usize| -> BootResult<(usize, usize)> {
+            let end = offset.checked_add(size).ok_or(BootError::BufferTooSmall)?;
+            if end > buffer.len() {
+                return Err(BootError::BufferTooSmall);
+            }
+            Ok((offset, size))
+        };
+
+        match self {
+            Self::V3Hdr(hdr) => {
+                let page_size = hdr.page_size as usize;
+                let mut offset = round_up_to_page_size(hdr.header_size as usize, page_size);
+                let vendor_ramdisk = checked_range(offset, hdr.vendor_ramdisk_size as usize)?;
+                offset += round_up_to_page_size(vendor_ramdisk.1, page_size);
+                let dtb = checked_range(offset, hdr.dtb_size as usize)?;
+                Ok(VendorBootLayout { vendor_ramdisk, dtb, ramdisk_table: None, bootconfig: None })
+            }
+            Self::V4Hdr(hdr) => {
+                let page_size = hdr.v3_img_hdr.page_size as usize;
+                let mut offset = round_up_to_page_size(hdr.v3_img_hdr.header_size as usize, page_size);
+                let vendor_ramdisk =
+                    checked_range(offset, hdr.v3_img_hdr.vendor_ramdisk_size as usize)?;
+                offset += round_up_to_page_size(vendor_ramdisk.1, page_size);
+                let dtb = checked_range(offset, hdr.v3_img_hdr.dtb_size as usize)?;
+                offset += round_up_to_page_size(dtb.1, page_size);
+                let ramdisk_table =
+                    checked_range(offset, hdr.vendor_ramdisk_table_size as usize)?;
+                offset += round_up_to_page_size(ramdisk_table.1, page_size);
+                let bootconfig = checked_range(offset, hdr.bootconfig_size as usize)?;
+                Ok(VendorBootLayout {
+                    vendor_ramdisk,
+                    dtb,
+                    ramdisk_table: Some(ramdisk_table),
+                    bootconfig: Some(bootconfig),
+                })
+            }
+        }
+    }
+}
+
 #[repr(C, packed)]
 #[derive(FromBytes, AsBytes, Debug, PartialEq, Copy, Clone)]
 /// The structure of the vendor boot image (introduced with version 3 and
@@ -745,6 +1103,1078 @@ impl<B: ByteSlice + PartialEq> VendorBootHdr<B> {
     }
 }
 
+/// Size in bytes of the bootconfig trailer magic string.
+pub const BOOTCONFIG_MAGIC_SIZE: usize = 12;
+/// Magic string terminating a bootconfig section, including the trailing newline.
+pub const BOOTCONFIG_MAGIC: [u8; BOOTCONFIG_MAGIC_SIZE] = *b"#BOOTCONFIG\n";
+/// Size in bytes of the `params_size` and `checksum` trailer fields.
+const BOOTCONFIG_TRAILER_FIELD_SIZE: usize = size_of::<u32>();
+/// Size in bytes of the full bootconfig trailer (`params_size` + `checksum` + magic).
+const BOOTCONFIG_TRAILER_SIZE: usize =
+    2 * BOOTCONFIG_TRAILER_FIELD_SIZE + BOOTCONFIG_MAGIC_SIZE;
+
+/// Computes the bootconfig checksum: an unsigned 32-bit wrapping sum of every
+/// byte of `params`.
+fn bootconfig_checksum(params: &[u8]) -> u32 {
+    params.iter().fold(0u32, |sum, &byte| sum.wrapping_add(byte.into()))
+}
+
+/// Appends a bootconfig trailer to `params` and returns the combined section.
+///
+/// The trailer layout is the raw `params` bytes, followed by the 4-byte
+/// little-endian `params_size`, the 4-byte little-endian `checksum`, and the
+/// 12-byte magic `#BOOTCONFIG\n`.
+pub fn build_bootconfig(params: &[u8]) -> Vec<u8> {
+    let mut section = Vec::with_capacity(params.len() + BOOTCONFIG_TRAILER_SIZE);
+    section.extend_from_slice(params);
+    section.extend_from_slice(&(params.len() as u32).to_le_bytes());
+    section.extend_from_slice(&bootconfig_checksum(params).to_le_bytes());
+    section.extend_from_slice(&BOOTCONFIG_MAGIC);
+    section
+}
+
+/// Locates and validates the bootconfig trailer at the end of `buffer`,
+/// returning the validated parameter slice on success.
+///
+/// # Errors
+///
+/// Returns `BootError::BadBootconfig` if the magic is missing, the claimed
+/// `params_size` overruns `buffer`, or the checksum does not match.
+pub fn parse_bootconfig_trailer(buffer: &[u8]) -> BootResult<&[u8]> {
+    if buffer.len() < BOOTCONFIG_TRAILER_SIZE {
+        return Err(BootError::BadBootconfig);
+    }
+    let magic_start = buffer.len() - BOOTCONFIG_MAGIC_SIZE;
+    if buffer[magic_start..] != BOOTCONFIG_MAGIC {
+        return Err(BootError::BadBootconfig);
+    }
+
+    let checksum_start = magic_start - BOOTCONFIG_TRAILER_FIELD_SIZE;
+    let size_start = checksum_start - BOOTCONFIG_TRAILER_FIELD_SIZE;
+    let params_size = u32::from_le_bytes(
+        buffer[size_start..checksum_start].try_into().map_err(|_| BootError::BadBootconfig)?,
+    ) as usize;
+    let checksum = u32::from_le_bytes(
+        buffer[checksum_start..magic_start].try_into().map_err(|_| BootError::BadBootconfig)?,
+    );
+
+    let params_start = size_start.checked_sub(params_size).ok_or(BootError::BadBootconfig)?;
+    let params = &buffer[params_start..size_start];
+    if bootconfig_checksum(params) != checksum {
+        return Err(BootError::BadBootconfig);
+    }
+    Ok(params)
+}
+
+/// Size in bytes of the padded vendor wrapper header used by several OEMs in
+/// front of the `ANDROID!`/`VNDRBOOT` magic.
+const VENDOR_WRAPPER_SIZE: usize = 512;
+
+/// MediaTek wrapper magic (4-byte little-endian) found at offset 0.
+const MTK_MAGIC: u32 = 0x8816_8858;
+/// Size in bytes of the MediaTek type name field.
+const MTK_TYPE_NAME_SIZE: usize = 32;
+
+/// DHTB wrapper magic found at offset 0: `DHTB` followed by a version byte
+/// (`\x01`), NUL-padded out to 8 bytes.
+const DHTB_MAGIC: [u8; 8] = *b"DHTB\x01\0\0\0";
+/// Size in bytes of the DHTB payload SHA-256 field.
+const DHTB_PAYLOAD_HASH_SIZE: usize = 40;
+
+/// Samsung SignBlob wrapper magic.
+const SIGNBLOB_MAGIC: [u8; 20] = *b"-SIGNED-BY-SIGNBLOB-";
+
+/// Byte offset of the Linux zImage magic within a raw, unwrapped zImage.
+const ZIMAGE_MAGIC_OFFSET: usize = 36;
+/// Linux zImage magic (4-byte little-endian).
+const ZIMAGE_MAGIC: u32 = 0x016f_2818;
+
+/// Describes a vendor-specific wrapper detected (or not) by
+/// `unwrap_vendor_header` in front of a boot/vendor_boot image.
+#[derive(PartialEq, Debug, Clone)]
+pub enum VendorWrapper {
+    /// No recognized wrapper; the buffer already starts with the inner image.
+    None,
+    /// MediaTek header: 4-byte magic, 4-byte content size, 32-byte type name,
+    /// padded to 512 bytes.
+    Mtk { content_size: u32, type_name: [u8; MTK_TYPE_NAME_SIZE] },
+    /// DHTB header: 8-byte magic, 40-byte payload SHA-256, 4-byte size,
+    /// padded to 512 bytes.
+    Dhtb { payload_sha256: [u8; DHTB_PAYLOAD_HASH_SIZE], size: u32 },
+    /// Samsung SignBlob header: 20-byte magic immediately preceding the image.
+    SamsungSignBlob,
+    /// A raw Linux zImage, identified by its magic at a fixed offset. Not a
+    /// wrapper to strip; signals that `buffer` is already the inner image.
+    ZImage,
+}
+
+/// Detects a vendor-specific wrapper at the front of `buffer` and returns it
+/// alongside the embedded boot/vendor_boot image slice, ready to hand to
+/// `BootImg::parse_boot_image` or `VendorBootHdr::parse_vendor_boot_image`.
+pub fn unwrap_vendor_header(buffer: &[u8]) -> (VendorWrapper, &[u8]) {
+    if buffer.len() >= VENDOR_WRAPPER_SIZE {
+        if u32::from_le_bytes(buffer[0..4].try_into().unwrap()) == MTK_MAGIC {
+            let content_size = u32::from_le_bytes(buffer[4..8].try_into().unwrap());
+            let mut type_name = [0u8; MTK_TYPE_NAME_SIZE];
+            type_name.copy_from_slice(&buffer[8..8 + MTK_TYPE_NAME_SIZE]);
+            return (
+                VendorWrapper::Mtk { content_size, type_name },
+                &buffer[VENDOR_WRAPPER_SIZE..],
+            );
+        }
+        if buffer[0..DHTB_MAGIC.len()] == DHTB_MAGIC {
+            let hash_start = DHTB_MAGIC.len();
+            let size_start = hash_start + DHTB_PAYLOAD_HASH_SIZE;
+            let mut payload_sha256 = [0u8; DHTB_PAYLOAD_HASH_SIZE];
+            payload_sha256.copy_from_slice(&buffer[hash_start..size_start]);
+            let size = u32::from_le_bytes(buffer[size_start..size_start + 4].try_into().unwrap());
+            return (
+                VendorWrapper::Dhtb { payload_sha256, size },
+                &buffer[VENDOR_WRAPPER_SIZE..],
+            );
+        }
+    }
+    if buffer.len() >= SIGNBLOB_MAGIC.len() && buffer[0..SIGNBLOB_MAGIC.len()] == SIGNBLOB_MAGIC {
+        return (VendorWrapper::SamsungSignBlob, &buffer[SIGNBLOB_MAGIC.len()..]);
+    }
+    if buffer.len() >= ZIMAGE_MAGIC_OFFSET + 4
+        && u32::from_le_bytes(
+            buffer[ZIMAGE_MAGIC_OFFSET..ZIMAGE_MAGIC_OFFSET + 4].try_into().unwrap(),
+        ) == ZIMAGE_MAGIC
+    {
+        return (VendorWrapper::ZImage, buffer);
+    }
+    (VendorWrapper::None, buffer)
+}
+
+/// Computes the DHTB payload digest written into `rewrap`'s output: with the
+/// `avb` feature, a SHA-256 of `inner` zero-padded out to
+/// `DHTB_PAYLOAD_HASH_SIZE` bytes; without it, `stored` unchanged, since this
+/// crate has no hashing dependency to recompute it with.
+#[cfg(feature = "avb")]
+fn dhtb_payload_sha256(
+    _stored: &[u8; DHTB_PAYLOAD_HASH_SIZE],
+    inner: &[u8],
+) -> [u8; DHTB_PAYLOAD_HASH_SIZE] {
+    use sha2::{Digest, Sha256};
+
+    let digest = Sha256::digest(inner);
+    let mut payload_sha256 = [0u8; DHTB_PAYLOAD_HASH_SIZE];
+    payload_sha256[..digest.len()].copy_from_slice(&digest);
+    payload_sha256
+}
+
+#[cfg(not(feature = "avb"))]
+fn dhtb_payload_sha256(
+    stored: &[u8; DHTB_PAYLOAD_HASH_SIZE],
+    _inner: &[u8],
+) -> [u8; DHTB_PAYLOAD_HASH_SIZE] {
+    *stored
+}
+
+/// Re-emits `inner` with the wrapper described by `wrapper`, recomputing the
+/// MTK/DHTB content size fields to match `inner`'s new length.
+///
+/// With the `avb` feature enabled, `VendorWrapper::Dhtb`'s `payload_sha256`
+/// is also recomputed from `inner`. Without it, this crate has no hashing
+/// dependency, so the stored `payload_sha256` is written back unchanged and
+/// callers that modify `inner` must recompute the digest themselves and
+/// update the field on `wrapper` before calling `rewrap`.
+pub fn rewrap(wrapper: &VendorWrapper, inner: &[u8]) -> Vec<u8> {
+    match wrapper {
+        VendorWrapper::None | VendorWrapper::ZImage => inner.to_vec(),
+        VendorWrapper::Mtk { type_name, .. } => {
+            let mut out = vec![0u8; VENDOR_WRAPPER_SIZE];
+            out[0..4].copy_from_slice(&MTK_MAGIC.to_le_bytes());
+            out[4..8].copy_from_slice(&(inner.len() as u32).to_le_bytes());
+            out[8..8 + MTK_TYPE_NAME_SIZE].copy_from_slice(type_name);
+            out.extend_from_slice(inner);
+            out
+        }
+        VendorWrapper::Dhtb { payload_sha256, .. } => {
+            let mut out = vec![0u8; VENDOR_WRAPPER_SIZE];
+            out[0..DHTB_MAGIC.len()].copy_from_slice(&DHTB_MAGIC);
+            let hash_start = DHTB_MAGIC.len();
+            let size_start = hash_start + DHTB_PAYLOAD_HASH_SIZE;
+            let hash = dhtb_payload_sha256(payload_sha256, inner);
+            out[hash_start..size_start].copy_from_slice(&hash);
+            out[size_start..size_start + 4].copy_from_slice(&(inner.len() as u32).to_le_bytes());
+            out.extend_from_slice(inner);
+            out
+        }
+        VendorWrapper::SamsungSignBlob => {
+            let mut out = Vec::with_capacity(SIGNBLOB_MAGIC.len() + inner.len());
+            out.extend_from_slice(&SIGNBLOB_MAGIC);
+            out.extend_from_slice(inner);
+            out
+        }
+    }
+}
+
+/// Appends zero padding to `buffer` until its length is a multiple of
+/// `page_size`.
+fn pad_to_page_size(buffer: &mut Vec<u8>, page_size: usize) {
+    let padded_len = round_up_to_page_size(buffer.len(), page_size);
+    buffer.resize(padded_len, 0);
+}
+
+/// Builds a complete, page-aligned boot image from header fields and payload
+/// sections: the write-side counterpart to `BootImg::parse_boot_image`.
+///
+/// Not every field applies to every `header_version`; fields that don't
+/// apply to the selected version are ignored by `build`.
+pub struct BootImageBuilder<'a> {
+    header_version: u32,
+    page_size: u32,
+    kernel: &'a [u8],
+    kernel_addr: u32,
+    ramdisk: &'a [u8],
+    ramdisk_addr: u32,
+    second: &'a [u8],
+    second_addr: u32,
+    recovery_dtbo: &'a [u8],
+    recovery_dtbo_offset: u64,
+    dtb: &'a [u8],
+    dtb_addr: u64,
+    signature: &'a [u8],
+    tags_addr: u32,
+    os_version: u32,
+    name: [u8; BOOT_NAME_SIZE],
+    cmdline: [u8; BOOT_ARGS_SIZE],
+    extra_cmdline: [u8; BOOT_EXTRA_ARGS_SIZE],
+    /// Timestamp / checksum / sha1 / etc, e.g. the SHA-1 digest mkbootimg
+    /// computes over the kernel/ramdisk/second sizes and contents. This
+    /// crate has no SHA-1 implementation, so callers that want the
+    /// conventional digest must compute it themselves and pass it here.
+    id: [u32; 8],
+}
+
+impl<'a> Default for BootImageBuilder<'a> {
+    fn default() -> Self {
+        Self {
+            header_version: 0,
+            page_size: 0,
+            kernel: &[],
+            kernel_addr: 0,
+            ramdisk: &[],
+            ramdisk_addr: 0,
+            second: &[],
+            second_addr: 0,
+            recovery_dtbo: &[],
+            recovery_dtbo_offset: 0,
+            dtb: &[],
+            dtb_addr: 0,
+            signature: &[],
+            tags_addr: 0,
+            os_version: 0,
+            name: [0; BOOT_NAME_SIZE],
+            cmdline: [0; BOOT_ARGS_SIZE],
+            extra_cmdline: [0; BOOT_EXTRA_ARGS_SIZE],
+            id: [0; 8],
+        }
+    }
+}
+
+impl<'a> BootImageBuilder<'a> {
+    /// Creates a builder for the given boot header version (0-4).
+    pub fn new(header_version: u32) -> Self {
+        Self { header_version, page_size: 4096, ..Default::default() }
+    }
+
+    /// Sets the flash page size; ignored for versions 3 and 4, which fix it
+    /// at 4096 bytes.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Sets the kernel payload and its physical load address.
+    pub fn kernel(mut self, kernel: &'a [u8], kernel_addr: u32) -> Self {
+        self.kernel = kernel;
+        self.kernel_addr = kernel_addr;
+        self
+    }
+
+    /// Sets the ramdisk payload and its physical load address.
+    pub fn ramdisk(mut self, ramdisk: &'a [u8], ramdisk_addr: u32) -> Self {
+        self.ramdisk = ramdisk;
+        self.ramdisk_addr = ramdisk_addr;
+        self
+    }
+
+    /// Sets the second-stage payload and its physical load address. Ignored
+    /// for versions 3 and 4, which have no second stage.
+    pub fn second(mut self, second: &'a [u8], second_addr: u32) -> Self {
+        self.second = second;
+        self.second_addr = second_addr;
+        self
+    }
+
+    /// Sets the recovery DTBO/ACPIO payload and its offset. Only used by
+    /// versions 1 and 2.
+    pub fn recovery_dtbo(mut self, recovery_dtbo: &'a [u8], recovery_dtbo_offset: u64) -> Self {
+        self.recovery_dtbo = recovery_dtbo;
+        self.recovery_dtbo_offset = recovery_dtbo_offset;
+        self
+    }
+
+    /// Sets the DTB payload and its physical load address. Only used by
+    /// version 2.
+    pub fn dtb(mut self, dtb: &'a [u8], dtb_addr: u64) -> Self {
+        self.dtb = dtb;
+        self.dtb_addr = dtb_addr;
+        self
+    }
+
+    /// Sets the boot signature (AVB) payload. Only used by version 4.
+    pub fn signature(mut self, signature: &'a [u8]) -> Self {
+        self.signature = signature;
+        self
+    }
+
+    /// Sets the physical address for kernel tags.
+    pub fn tags_addr(mut self, tags_addr: u32) -> Self {
+        self.tags_addr = tags_addr;
+        self
+    }
+
+    /// Sets the operating system version and security patch level; see
+    /// `os_version`.
+    pub fn os_version(mut self, os_version: u32) -> Self {
+        self.os_version = os_version;
+        self
+    }
+
+    /// Sets the kernel commandline, truncating to `BOOT_ARGS_SIZE` +
+    /// `BOOT_EXTRA_ARGS_SIZE` bytes. Versions 0-2 write all of it (the part
+    /// past `BOOT_ARGS_SIZE` into `extra_cmdline`); versions 3 and 4 write
+    /// only the first `BOOT_ARGS_SIZE` bytes.
+    pub fn cmdline(mut self, cmdline: &[u8]) -> Self {
+        let (head, tail) = cmdline.split_at(cmdline.len().min(BOOT_ARGS_SIZE));
+        self.cmdline[..head.len()].copy_from_slice(head);
+        let tail = &tail[..tail.len().min(BOOT_EXTRA_ARGS_SIZE)];
+        self.extra_cmdline[..tail.len()].copy_from_slice(tail);
+        self
+    }
+
+    /// Sets the asciiz product name, truncating to `BOOT_NAME_SIZE` bytes.
+    pub fn name(mut self, name: &[u8]) -> Self {
+        let name = &name[..name.len().min(BOOT_NAME_SIZE)];
+        self.name[..name.len()].copy_from_slice(name);
+        self
+    }
+
+    /// Sets the `id` header field directly, e.g. a pre-computed SHA-1
+    /// digest.
+    pub fn id(mut self, id: [u32; 8]) -> Self {
+        self.id = id;
+        self
+    }
+
+    /// Serializes the configured fields into a complete boot image.
+    ///
+    /// Always returns an in-memory `Vec<u8>` rather than writing to an
+    /// arbitrary `io::Write`, and never computes `id` itself (see
+    /// [`BootImageBuilder::id`]) — both would need a dependency this crate
+    /// deliberately doesn't have.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BootError::UnknownVersion` if `header_version` is not 0-4.
+    pub fn build(&self) -> BootResult<Vec<u8>> {
+        match self.header_version {
+            0..=2 => self.build_v0_v2(),
+            3 => self.build_v3(),
+            4 => self.build_v4(),
+            _ => Err(BootError::UnknownVersion),
+        }
+    }
+
+    fn v0_hdr(&self) -> BootImgHdrV0 {
+        BootImgHdrV0 {
+            magic: BOOT_MAGIC,
+            kernel_size: self.kernel.len() as u32,
+            kernel_addr: self.kernel_addr,
+            ramdisk_size: self.ramdisk.len() as u32,
+            ramdisk_addr: self.ramdisk_addr,
+            second_size: self.second.len() as u32,
+            second_addr: self.second_addr,
+            tags_addr: self.tags_addr,
+            page_size: self.page_size,
+            header_version: self.header_version,
+            os_version: self.os_version,
+            name: self.name,
+            cmdline: self.cmdline,
+            id: self.id,
+            extra_cmdline: self.extra_cmdline,
+        }
+    }
+
+    fn build_v0_v2(&self) -> BootResult<Vec<u8>> {
+        let page_size = self.page_size as usize;
+        let mut out = Vec::new();
+        match self.header_version {
+            0 => out.extend_from_slice(self.v0_hdr().as_bytes()),
+            1 => out.extend_from_slice(
+                BootImgHdrV1 {
+                    v0_hdr: self.v0_hdr(),
+                    recovery_dtbo_size: self.recovery_dtbo.len() as u32,
+                    recovery_dtbo_offset: self.recovery_dtbo_offset,
+                    header_size: size_of::<BootImgHdrV1>() as u32,
+                }
+                .as_bytes(),
+            ),
+            2 => out.extend_from_slice(
+                BootImgHdrV2 {
+                    v1_hdr: BootImgHdrV1 {
+                        v0_hdr: self.v0_hdr(),
+                        recovery_dtbo_size: self.recovery_dtbo.len() as u32,
+                        recovery_dtbo_offset: self.recovery_dtbo_offset,
+                        header_size: size_of::<BootImgHdrV2>() as u32,
+                    },
+                    dtb_size: self.dtb.len() as u32,
+                    dtb_addr: self.dtb_addr,
+                }
+                .as_bytes(),
+            ),
+            _ => unreachable!(),
+        }
+        pad_to_page_size(&mut out, page_size);
+
+        out.extend_from_slice(self.kernel);
+        pad_to_page_size(&mut out, page_size);
+        out.extend_from_slice(self.ramdisk);
+        pad_to_page_size(&mut out, page_size);
+        out.extend_from_slice(self.second);
+        pad_to_page_size(&mut out, page_size);
+        if self.header_version >= 1 {
+            out.extend_from_slice(self.recovery_dtbo);
+            pad_to_page_size(&mut out, page_size);
+        }
+        if self.header_version >= 2 {
+            out.extend_from_slice(self.dtb);
+            pad_to_page_size(&mut out, page_size);
+        }
+        Ok(out)
+    }
+
+    fn build_v3(&self) -> BootResult<Vec<u8>> {
+        const PAGE_SIZE: usize = 4096;
+        let hdr = BootImgHdrV3 {
+            magic: BOOT_MAGIC,
+            kernel_size: self.kernel.len() as u32,
+            ramdisk_size: self.ramdisk.len() as u32,
+            os_version: self.os_version,
+            header_size: size_of::<BootImgHdrV3>() as u32,
+            reserved: [0; 4],
+            header_version: 3,
+            page_size: PAGE_SIZE as u32,
+            kernel_addr: 0,
+            ramdisk_addr: 0,
+            vendor_ramdisk_size: 0,
+            cmdline: {
+                let mut cmdline = [0u8; VENDOR_BOOT_ARGS_SIZE];
+                cmdline[..BOOT_ARGS_SIZE].copy_from_slice(&self.cmdline);
+                cmdline
+            },
+            tags_addr: self.tags_addr,
+            name: [0; VENDOR_BOOT_NAME_SIZE],
+            dtb_size: 0,
+            dtb_addr: 0,
+        };
+        let mut out = Vec::new();
+        out.extend_from_slice(hdr.as_bytes());
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        out.extend_from_slice(self.kernel);
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        out.extend_from_slice(self.ramdisk);
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        Ok(out)
+    }
+
+    fn build_v4(&self) -> BootResult<Vec<u8>> {
+        const PAGE_SIZE: usize = 4096;
+        let hdr = BootImgHdrV4 {
+            v3_hdr: BootImgHdrV3 {
+                magic: BOOT_MAGIC,
+                kernel_size: self.kernel.len() as u32,
+                ramdisk_size: self.ramdisk.len() as u32,
+                os_version: self.os_version,
+                header_size: size_of::<BootImgHdrV4>() as u32,
+                reserved: [0; 4],
+                header_version: 4,
+                page_size: PAGE_SIZE as u32,
+                kernel_addr: 0,
+                ramdisk_addr: 0,
+                vendor_ramdisk_size: 0,
+                cmdline: {
+                    let mut cmdline = [0u8; VENDOR_BOOT_ARGS_SIZE];
+                    cmdline[..BOOT_ARGS_SIZE].copy_from_slice(&self.cmdline);
+                    cmdline
+                },
+                tags_addr: self.tags_addr,
+                name: [0; VENDOR_BOOT_NAME_SIZE],
+                dtb_size: 0,
+                dtb_addr: 0,
+            },
+            signature_size: self.signature.len() as u32,
+        };
+        let mut out = Vec::new();
+        out.extend_from_slice(hdr.as_bytes());
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        out.extend_from_slice(self.kernel);
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        out.extend_from_slice(self.ramdisk);
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        out.extend_from_slice(self.signature);
+        pad_to_page_size(&mut out, PAGE_SIZE);
+        Ok(out)
+    }
+}
+
+/// One vendor ramdisk to be emitted by `VendorBootImageBuilder`, and its
+/// vendor ramdisk table metadata (version 4 only).
+pub struct VendorRamdiskFragment<'a> {
+    /// Ramdisk contents.
+    pub data: &'a [u8],
+    /// Type of the ramdisk; see `VendorRamdiskType`.
+    pub ramdisk_type: VendorRamdiskType,
+    /// Asciiz ramdisk name.
+    pub name: [u8; VENDOR_RAMDISK_NAME_SIZE],
+    /// Hardware identifiers describing the board, soc or platform this
+    /// ramdisk is intended to be loaded on.
+    pub board_id: [u32; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+}
+
+/// Builds a complete, page-aligned vendor boot image from header fields and
+/// payload sections: the write-side counterpart to
+/// `VendorBootHdr::parse_vendor_boot_image`.
+///
+/// For `header_version` 4, multiple ramdisks added with `add_ramdisk` are
+/// concatenated into the vendor ramdisk section and indexed by a generated
+/// vendor ramdisk table; for version 3, only the first ramdisk's `data` is
+/// used, as version 3 has no table.
+pub struct VendorBootImageBuilder<'a> {
+    header_version: u32,
+    page_size: u32,
+    kernel_addr: u32,
+    ramdisk_addr: u32,
+    tags_addr: u32,
+    os_version: u32,
+    cmdline: [u8; VENDOR_BOOT_ARGS_SIZE],
+    name: [u8; VENDOR_BOOT_NAME_SIZE],
+    dtb: &'a [u8],
+    dtb_addr: u64,
+    ramdisks: Vec<VendorRamdiskFragment<'a>>,
+    bootconfig_params: &'a [u8],
+}
+
+impl<'a> VendorBootImageBuilder<'a> {
+    /// Creates a builder for the given vendor boot header version (3 or 4).
+    pub fn new(header_version: u32) -> Self {
+        Self {
+            header_version,
+            page_size: 4096,
+            kernel_addr: 0,
+            ramdisk_addr: 0,
+            tags_addr: 0,
+            os_version: 0,
+            cmdline: [0; VENDOR_BOOT_ARGS_SIZE],
+            name: [0; VENDOR_BOOT_NAME_SIZE],
+            dtb: &[],
+            dtb_addr: 0,
+            ramdisks: Vec::new(),
+            bootconfig_params: &[],
+        }
+    }
+
+    /// Sets the flash page size.
+    pub fn page_size(mut self, page_size: u32) -> Self {
+        self.page_size = page_size;
+        self
+    }
+
+    /// Sets the kernel and ramdisk physical load addresses.
+    pub fn load_addrs(mut self, kernel_addr: u32, ramdisk_addr: u32, tags_addr: u32) -> Self {
+        self.kernel_addr = kernel_addr;
+        self.ramdisk_addr = ramdisk_addr;
+        self.tags_addr = tags_addr;
+        self
+    }
+
+    /// Sets the operating system version and security patch level; see
+    /// `os_version`.
+    pub fn os_version(mut self, os_version: u32) -> Self {
+        self.os_version = os_version;
+        self
+    }
+
+    /// Sets the vendor kernel commandline, truncating to
+    /// `VENDOR_BOOT_ARGS_SIZE` bytes.
+    pub fn cmdline(mut self, cmdline: &[u8]) -> Self {
+        let cmdline = &cmdline[..cmdline.len().min(VENDOR_BOOT_ARGS_SIZE)];
+        self.cmdline[..cmdline.len()].copy_from_slice(cmdline);
+        self
+    }
+
+    /// Sets the asciiz product name, truncating to `VENDOR_BOOT_NAME_SIZE`
+    /// bytes.
+    pub fn name(mut self, name: &[u8]) -> Self {
+        let name = &name[..name.len().min(VENDOR_BOOT_NAME_SIZE)];
+        self.name[..name.len()].copy_from_slice(name);
+        self
+    }
+
+    /// Sets the DTB payload and its physical load address.
+    pub fn dtb(mut self, dtb: &'a [u8], dtb_addr: u64) -> Self {
+        self.dtb = dtb;
+        self.dtb_addr = dtb_addr;
+        self
+    }
+
+    /// Appends a vendor ramdisk. For version 4, each call adds an entry to
+    /// the generated vendor ramdisk table; for version 3, only the first
+    /// call's `data` ends up in the image.
+    pub fn add_ramdisk(mut self, ramdisk: VendorRamdiskFragment<'a>) -> Self {
+        self.ramdisks.push(ramdisk);
+        self
+    }
+
+    /// Sets the build-time bootconfig parameters. Only used by version 4;
+    /// `build` appends the trailer via `build_bootconfig`.
+    pub fn bootconfig_params(mut self, params: &'a [u8]) -> Self {
+        self.bootconfig_params = params;
+        self
+    }
+
+    /// Serializes the configured fields into a complete vendor boot image.
+    ///
+    /// # Errors
+    ///
+    /// Returns `BootError::UnknownVersion` if `header_version` is not 3 or 4.
+    pub fn build(&self) -> BootResult<Vec<u8>> {
+        match self.header_version {
+            3 => self.build_v3(),
+            4 => self.build_v4(),
+            _ => Err(BootError::UnknownVersion),
+        }
+    }
+
+    fn vendor_ramdisk_size(&self) -> usize {
+        self.ramdisks.iter().map(|fragment| fragment.data.len()).sum()
+    }
+
+    fn build_v3(&self) -> BootResult<Vec<u8>> {
+        let page_size = self.page_size as usize;
+        let vendor_ramdisk = self.ramdisks.first().map(|fragment| fragment.data).unwrap_or(&[]);
+        let hdr = VendorBootHdrV3 {
+            magic: VENDOR_BOOT_MAGIC,
+            header_version: 3,
+            page_size: self.page_size,
+            kernel_addr: self.kernel_addr,
+            ramdisk_addr: self.ramdisk_addr,
+            vendor_ramdisk_size: vendor_ramdisk.len() as u32,
+            cmdline: self.cmdline,
+            tags_addr: self.tags_addr,
+            name: self.name,
+            header_size: size_of::<VendorBootHdrV3>() as u32,
+            dtb_size: self.dtb.len() as u32,
+            dtb_addr: self.dtb_addr,
+        };
+        let mut out = Vec::new();
+        out.extend_from_slice(hdr.as_bytes());
+        pad_to_page_size(&mut out, page_size);
+        out.extend_from_slice(vendor_ramdisk);
+        pad_to_page_size(&mut out, page_size);
+        out.extend_from_slice(self.dtb);
+        pad_to_page_size(&mut out, page_size);
+        Ok(out)
+    }
+
+    fn build_v4(&self) -> BootResult<Vec<u8>> {
+        let page_size = self.page_size as usize;
+        let entry_size = size_of::<VendorRamdiskTableEntryV4>();
+        let table_size = self.ramdisks.len() * entry_size;
+        let bootconfig = build_bootconfig(self.bootconfig_params);
+
+        let hdr = VendorBootHdrV4 {
+            v3_img_hdr: VendorBootHdrV3 {
+                magic: VENDOR_BOOT_MAGIC,
+                header_version: 4,
+                page_size: self.page_size,
+                kernel_addr: self.kernel_addr,
+                ramdisk_addr: self.ramdisk_addr,
+                vendor_ramdisk_size: self.vendor_ramdisk_size() as u32,
+                cmdline: self.cmdline,
+                tags_addr: self.tags_addr,
+                name: self.name,
+                header_size: size_of::<VendorBootHdrV4>() as u32,
+                dtb_size: self.dtb.len() as u32,
+                dtb_addr: self.dtb_addr,
+            },
+            vendor_ramdisk_table_size: table_size as u32,
+            vendor_ramdisk_table_entry_num: self.ramdisks.len() as u32,
+            vendor_ramdisk_table_entry_size: entry_size as u32,
+            bootconfig_size: bootconfig.len() as u32,
+        };
+        let mut out = Vec::new();
+        out.extend_from_slice(hdr.as_bytes());
+        pad_to_page_size(&mut out, page_size);
+
+        let mut ramdisk_offset = 0u32;
+        let mut table = Vec::with_capacity(table_size);
+        for fragment in &self.ramdisks {
+            out.extend_from_slice(fragment.data);
+            table.extend_from_slice(
+                VendorRamdiskTableEntryV4 {
+                    ramdisk_size: fragment.data.len() as u32,
+                    ramdisk_offset,
+                    ramdisk_type: fragment.ramdisk_type,
+                    ramdisk_name: fragment.name,
+                    board_id: fragment.board_id,
+                }
+                .as_bytes(),
+            );
+            ramdisk_offset += fragment.data.len() as u32;
+        }
+        pad_to_page_size(&mut out, page_size);
+
+        out.extend_from_slice(self.dtb);
+        pad_to_page_size(&mut out, page_size);
+
+        out.extend_from_slice(&table);
+        pad_to_page_size(&mut out, page_size);
+
+        out.extend_from_slice(&bootconfig);
+        pad_to_page_size(&mut out, page_size);
+
+        Ok(out)
+    }
+}
+
+/// Selects which vendor ramdisk fragment to replace in
+/// `replace_vendor_ramdisk`.
+#[derive(Debug, Clone, Copy)]
+pub enum RamdiskSelector<'a> {
+    /// Select the entry with this `ramdisk_type`.
+    Type(VendorRamdiskType),
+    /// Select the entry whose asciiz `ramdisk_name` matches, compared up to
+    /// the first NUL.
+    Name(&'a [u8]),
+}
+
+/// Ports the ramdisk-replacement workflow from fastboot's
+/// `vendor_boot_img_utils.cpp` `DataUpdater`: given the full bytes of a
+/// version 4 vendor boot image, replaces the vendor ramdisk fragment matched
+/// by `selector` with `new_ramdisk` and returns the rebuilt image.
+///
+/// The header, and the DTB, ramdisk table, and bootconfig sections, are
+/// copied through unchanged (contents verbatim, offsets shifted to stay
+/// 4096-byte page aligned); only the target entry's `ramdisk_size` and every
+/// following entry's `ramdisk_offset` are adjusted, along with the header's
+/// `vendor_ramdisk_size`.
+///
+/// # Errors
+///
+/// Returns `BootError::UnknownVersion` if `buffer` is not a version 4 vendor
+/// boot image, and `BootError::RamdiskNotFound` if no entry matches
+/// `selector`.
+pub fn replace_vendor_ramdisk(
+    buffer: &[u8],
+    selector: RamdiskSelector,
+    new_ramdisk: &[u8],
+) -> BootResult<Vec<u8>> {
+    const PAGE_SIZE: usize = 4096;
+
+    let header = VendorBootHdr::parse_vendor_boot_image(buffer)?;
+    let hdr = match &header {
+        VendorBootHdr::V4Hdr(hdr) => hdr,
+        VendorBootHdr::V3Hdr(_) => return Err(BootError::UnknownVersion),
+    };
+    let layout = header.layout(buffer)?;
+
+    let entries: Vec<VendorRamdiskTableEntryV4> =
+        header.ramdisk_table_entries(buffer)?.map(|entry| *entry).collect();
+    let target = entries
+        .iter()
+        .position(|entry| match selector {
+            RamdiskSelector::Type(ramdisk_type) => {
+                let entry_type = entry.ramdisk_type;
+                entry_type == ramdisk_type
+            }
+            RamdiskSelector::Name(name) => {
+                let entry_name = entry.ramdisk_name.split(|&b| b == 0).next().unwrap();
+                let name = name.split(|&b| b == 0).next().unwrap();
+                entry_name == name
+            }
+        })
+        .ok_or(BootError::RamdiskNotFound)?;
+
+    let (vendor_ramdisk_start, _) = layout.vendor_ramdisk;
+    let old_target = &entries[target];
+    let old_target_start = vendor_ramdisk_start + old_target.ramdisk_offset as usize;
+    let old_target_end = old_target_start + old_target.ramdisk_size as usize;
+
+    let mut new_vendor_ramdisk = Vec::new();
+    new_vendor_ramdisk.extend_from_slice(&buffer[vendor_ramdisk_start..old_target_start]);
+    new_vendor_ramdisk.extend_from_slice(new_ramdisk);
+    new_vendor_ramdisk
+        .extend_from_slice(&buffer[old_target_end..vendor_ramdisk_start + layout.vendor_ramdisk.1]);
+
+    let delta = new_ramdisk.len() as i64 - old_target.ramdisk_size as i64;
+    let mut new_entries = entries;
+    new_entries[target].ramdisk_size = new_ramdisk.len() as u32;
+    for entry in &mut new_entries[target + 1..] {
+        entry.ramdisk_offset = (entry.ramdisk_offset as i64 + delta) as u32;
+    }
+    let new_vendor_ramdisk_size = new_vendor_ramdisk.len() as u32;
+
+    let new_hdr = VendorBootHdrV4 {
+        v3_img_hdr: VendorBootHdrV3 {
+            magic: VENDOR_BOOT_MAGIC,
+            header_version: 4,
+            page_size: hdr.v3_img_hdr.page_size,
+            kernel_addr: hdr.v3_img_hdr.kernel_addr,
+            ramdisk_addr: hdr.v3_img_hdr.ramdisk_addr,
+            vendor_ramdisk_size: new_vendor_ramdisk_size,
+            cmdline: hdr.v3_img_hdr.cmdline,
+            tags_addr: hdr.v3_img_hdr.tags_addr,
+            name: hdr.v3_img_hdr.name,
+            header_size: hdr.v3_img_hdr.header_size,
+            dtb_size: hdr.v3_img_hdr.dtb_size,
+            dtb_addr: hdr.v3_img_hdr.dtb_addr,
+        },
+        vendor_ramdisk_table_size: hdr.vendor_ramdisk_table_size,
+        vendor_ramdisk_table_entry_num: hdr.vendor_ramdisk_table_entry_num,
+        vendor_ramdisk_table_entry_size: hdr.vendor_ramdisk_table_entry_size,
+        bootconfig_size: hdr.bootconfig_size,
+    };
+
+    let mut out = Vec::new();
+    out.extend_from_slice(new_hdr.as_bytes());
+    pad_to_page_size(&mut out, PAGE_SIZE);
+
+    out.extend_from_slice(&new_vendor_ramdisk);
+    pad_to_page_size(&mut out, PAGE_SIZE);
+
+    let (dtb_start, dtb_size) = layout.dtb;
+    out.extend_from_slice(&buffer[dtb_start..dtb_start + dtb_size]);
+    pad_to_page_size(&mut out, PAGE_SIZE);
+
+    for entry in &new_entries {
+        out.extend_from_slice(entry.as_bytes());
+    }
+    pad_to_page_size(&mut out, PAGE_SIZE);
+
+    let (bootconfig_start, bootconfig_size) = layout.bootconfig.unwrap_or((0, 0));
+    out.extend_from_slice(&buffer[bootconfig_start..bootconfig_start + bootconfig_size]);
+    pad_to_page_size(&mut out, PAGE_SIZE);
+
+    Ok(out)
+}
+
+/// Size in bytes of the `AvbFooter` structure.
+const AVB_FOOTER_SIZE: usize = 64;
+/// `AvbFooter` magic string.
+const AVB_FOOTER_MAGIC: [u8; 4] = *b"AVBf";
+/// Size in bytes of the `AvbVBMetaImageHeader` structure.
+const AVB_VBMETA_HEADER_SIZE: usize = 256;
+/// `AvbDescriptor` tag identifying an `AvbHashDescriptor`.
+const AVB_DESCRIPTOR_TAG_HASH: u64 = 1;
+/// Size in bytes of an `AvbDescriptor`'s shared `tag` + `num_bytes_following`
+/// prefix.
+const AVB_DESCRIPTOR_HEADER_SIZE: usize = 16;
+/// Size in bytes of the fixed-size fields of an `AvbHashDescriptor`,
+/// following the shared `AvbDescriptor` prefix.
+const AVB_HASH_DESCRIPTOR_FIXED_SIZE: usize = 116;
+
+/// Android Verified Boot footer, found in the last `AVB_FOOTER_SIZE` bytes of
+/// a partition that carries AVB metadata appended out-of-band (e.g. GKI boot
+/// and vendor_boot images).
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AvbFooter {
+    /// Major version of the footer format.
+    pub version_major: u32,
+    /// Minor version of the footer format.
+    pub version_minor: u32,
+    /// Size of the original image, before the footer and any partition
+    /// padding were appended.
+    pub original_image_size: u64,
+    /// Offset of the vbmeta image, relative to the start of the partition.
+    pub vbmeta_offset: u64,
+    /// Size of the vbmeta image.
+    pub vbmeta_size: u64,
+}
+
+/// Scans the tail of `buffer` for an `AvbFooter`.
+///
+/// # Errors
+///
+/// Returns `BootError::BadAvbFooter` if `buffer` is too small or the magic
+/// does not match.
+pub fn find_avb_footer(buffer: &[u8]) -> BootResult<AvbFooter> {
+    if buffer.len() < AVB_FOOTER_SIZE {
+        return Err(BootError::BadAvbFooter);
+    }
+    let footer = &buffer[buffer.len() - AVB_FOOTER_SIZE..];
+    if footer[0..4] != AVB_FOOTER_MAGIC {
+        return Err(BootError::BadAvbFooter);
+    }
+    let be_u32 = |range: core::ops::Range<usize>| u32::from_be_bytes(footer[range].try_into().unwrap());
+    let be_u64 = |range: core::ops::Range<usize>| u64::from_be_bytes(footer[range].try_into().unwrap());
+    Ok(AvbFooter {
+        version_major: be_u32(4..8),
+        version_minor: be_u32(8..12),
+        original_image_size: be_u64(12..20),
+        vbmeta_offset: be_u64(20..28),
+        vbmeta_size: be_u64(28..36),
+    })
+}
+
+/// The hash descriptor decoded from a vbmeta image's descriptor list: the
+/// subset of `AvbHashDescriptor` needed to verify a partition's digest.
+#[derive(Debug, PartialEq, Eq, Clone, Copy)]
+pub struct AvbHashDescriptor<'a> {
+    /// Number of bytes of the partition that were hashed.
+    pub image_size: u64,
+    /// Asciiz hash algorithm name, e.g. `"sha256"` or `"sha512"`.
+    pub hash_algorithm: &'a str,
+    /// Salt prepended to the image before hashing.
+    pub salt: &'a [u8],
+    /// Expected digest.
+    pub digest: &'a [u8],
+}
+
+/// Parses `vbmeta` (the region described by `AvbFooter::vbmeta_offset`/
+/// `vbmeta_size`) and returns its first hash descriptor.
+///
+/// This decodes only as much of the vbmeta header and descriptor framing as
+/// is needed to locate a hash descriptor; it does not verify the vbmeta
+/// authentication/signature block.
+///
+/// # Errors
+///
+/// Returns `BootError::BadAvbFooter` if the vbmeta magic doesn't match, the
+/// header's block sizes/offsets don't fit within `vbmeta`, or no hash
+/// descriptor is present.
+pub fn parse_avb_hash_descriptor(vbmeta: &[u8]) -> BootResult<AvbHashDescriptor<'_>> {
+    if vbmeta.len() < AVB_VBMETA_HEADER_SIZE || vbmeta[0..4] != *b"AVB0" {
+        return Err(BootError::BadAvbFooter);
+    }
+    let be_u64 = |range: core::ops::Range<usize>| u64::from_be_bytes(vbmeta[range].try_into().unwrap());
+
+    let authentication_data_block_size = be_u64(12..20) as usize;
+    let descriptors_offset = be_u64(96..104) as usize;
+    let descriptors_size = be_u64(104..112) as usize;
+
+    let aux_block_start = AVB_VBMETA_HEADER_SIZE
+        .checked_add(authentication_data_block_size)
+        .ok_or(BootError::BadAvbFooter)?;
+    let descriptors_start =
+        aux_block_start.checked_add(descriptors_offset).ok_or(BootError::BadAvbFooter)?;
+    let descriptors_end =
+        descriptors_start.checked_add(descriptors_size).ok_or(BootError::BadAvbFooter)?;
+    if descriptors_end > vbmeta.len() {
+        return Err(BootError::BadAvbFooter);
+    }
+    let descriptors = &vbmeta[descriptors_start..descriptors_end];
+
+    let mut offset = 0;
+    while offset + AVB_DESCRIPTOR_HEADER_SIZE <= descriptors.len() {
+        let tag = u64::from_be_bytes(descriptors[offset..offset + 8].try_into().unwrap());
+        let num_bytes_following =
+            u64::from_be_bytes(descriptors[offset + 8..offset + 16].try_into().unwrap()) as usize;
+        let descriptor_start = offset + AVB_DESCRIPTOR_HEADER_SIZE;
+        let descriptor_end =
+            descriptor_start.checked_add(num_bytes_following).ok_or(BootError::BadAvbFooter)?;
+        if descriptor_end > descriptors.len() {
+            return Err(BootError::BadAvbFooter);
+        }
+
+        if tag == AVB_DESCRIPTOR_TAG_HASH {
+            let descriptor = &descriptors[descriptor_start..descriptor_end];
+            if descriptor.len() < AVB_HASH_DESCRIPTOR_FIXED_SIZE {
+                return Err(BootError::BadAvbFooter);
+            }
+            let image_size = u64::from_be_bytes(descriptor[0..8].try_into().unwrap());
+            let hash_algorithm_field = &descriptor[8..40];
+            let hash_algorithm_len =
+                hash_algorithm_field.iter().position(|&b| b == 0).unwrap_or(hash_algorithm_field.len());
+            let hash_algorithm = core::str::from_utf8(&hash_algorithm_field[..hash_algorithm_len])
+                .map_err(|_| BootError::BadAvbFooter)?;
+            let partition_name_len =
+                u32::from_be_bytes(descriptor[40..44].try_into().unwrap()) as usize;
+            let salt_len = u32::from_be_bytes(descriptor[44..48].try_into().unwrap()) as usize;
+            let digest_len = u32::from_be_bytes(descriptor[48..52].try_into().unwrap()) as usize;
+
+            let salt_start = AVB_HASH_DESCRIPTOR_FIXED_SIZE
+                .checked_add(partition_name_len)
+                .ok_or(BootError::BadAvbFooter)?;
+            let digest_start = salt_start.checked_add(salt_len).ok_or(BootError::BadAvbFooter)?;
+            let digest_end = digest_start.checked_add(digest_len).ok_or(BootError::BadAvbFooter)?;
+            if digest_end > descriptor.len() {
+                return Err(BootError::BadAvbFooter);
+            }
+
+            return Ok(AvbHashDescriptor {
+                image_size,
+                hash_algorithm,
+                salt: &descriptor[salt_start..digest_start],
+                digest: &descriptor[digest_start..digest_end],
+            });
+        }
+
+        offset = descriptor_end;
+    }
+    Err(BootError::BadAvbFooter)
+}
+
+/// Verifies `image` against an `AvbHashDescriptor`'s salt and expected
+/// digest.
+///
+/// The digest backend lives entirely behind the `avb` feature so the core
+/// crate stays dependency-free without it; enabling `avb` pulls in `sha2`
+/// to implement the `"sha256"`/`"sha512"` algorithms named by the
+/// descriptor.
+///
+/// # Errors
+///
+/// Returns `BootError::VerificationFailed` if `image` is shorter than
+/// `descriptor.image_size`, the algorithm name is unrecognized, or the
+/// computed digest doesn't match `descriptor.digest`.
+#[cfg(feature = "avb")]
+pub fn verify_hash(descriptor: &AvbHashDescriptor, image: &[u8]) -> BootResult<()> {
+    use sha2::{Digest, Sha256, Sha512};
+
+    let image_size = descriptor.image_size as usize;
+    if image_size > image.len() {
+        return Err(BootError::VerificationFailed);
+    }
+    let hashed = &image[..image_size];
+
+    let computed: Vec<u8> = match descriptor.hash_algorithm {
+        "sha256" => {
+            let mut hasher = Sha256::new();
+            hasher.update(descriptor.salt);
+            hasher.update(hashed);
+            hasher.finalize().to_vec()
+        }
+        "sha512" => {
+            let mut hasher = Sha512::new();
+            hasher.update(descriptor.salt);
+            hasher.update(hashed);
+            hasher.finalize().to_vec()
+        }
+        _ => return Err(BootError::VerificationFailed),
+    };
+
+    if computed == descriptor.digest {
+        Ok(())
+    } else {
+        Err(BootError::VerificationFailed)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -903,4 +2333,667 @@ mod tests {
         ));
         assert_eq!(VendorBootHdr::parse_vendor_boot_image(&buffer[..]), expected);
     }
+
+    #[test]
+    fn ramdisk_table_entries_v4() {
+        const PAGE_SIZE: usize = 4096;
+        const ENTRY_NUM: usize = 2;
+        const VENDOR_RAMDISK_SIZE: usize = 1234;
+        let table_offset = round_up_to_page_size(size_of::<VendorBootHdrV4>(), PAGE_SIZE)
+            + round_up_to_page_size(VENDOR_RAMDISK_SIZE, PAGE_SIZE);
+        let table_size = ENTRY_NUM * size_of::<VendorRamdiskTableEntryV4>();
+        let mut buffer = vec![0u8; table_offset + table_size];
+        add::<VendorBootHdrV4>(
+            &mut buffer,
+            VendorBootHdrV4 {
+                v3_img_hdr: VendorBootHdrV3 {
+                    header_version: 4,
+                    page_size: PAGE_SIZE as u32,
+                    vendor_ramdisk_size: VENDOR_RAMDISK_SIZE as u32,
+                    ..Default::default()
+                },
+                vendor_ramdisk_table_size: table_size as u32,
+                vendor_ramdisk_table_entry_num: ENTRY_NUM as u32,
+                vendor_ramdisk_table_entry_size: size_of::<VendorRamdiskTableEntryV4>() as u32,
+                ..Default::default()
+            },
+        );
+        add::<VendorRamdiskTableEntryV4>(
+            &mut buffer[table_offset..],
+            VendorRamdiskTableEntryV4 {
+                ramdisk_size: 1234,
+                ramdisk_offset: 0,
+                ramdisk_type: VendorRamdiskType::PLATFORM,
+                ramdisk_name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            },
+        );
+
+        let header = VendorBootHdr::parse_vendor_boot_image(&buffer[..]).unwrap();
+        let entries: Vec<_> = header.ramdisk_table_entries(&buffer).unwrap().collect();
+        assert_eq!(entries.len(), ENTRY_NUM);
+        let (ramdisk_size, ramdisk_type) = (entries[0].ramdisk_size, entries[0].ramdisk_type);
+        assert_eq!(ramdisk_size, 1234);
+        assert_eq!(ramdisk_type, VendorRamdiskType::PLATFORM);
+    }
+
+    #[test]
+    fn ramdisk_table_entries_v3_unsupported() {
+        let mut buffer = [0; core::mem::size_of::<VendorBootHdrV3>()];
+        add::<VendorBootHdrV3>(&mut buffer, Default::default());
+        let header = VendorBootHdr::parse_vendor_boot_image(&buffer[..]).unwrap();
+        assert_eq!(header.ramdisk_table_entries(&buffer).err(), Some(BootError::UnknownVersion));
+    }
+
+    #[test]
+    fn ramdisk_table_entry_size_too_small() {
+        let mut buffer = [0; core::mem::size_of::<VendorBootHdrV4>()];
+        add::<VendorBootHdrV4>(
+            &mut buffer,
+            VendorBootHdrV4 { vendor_ramdisk_table_entry_size: 1, ..Default::default() },
+        );
+        let header = VendorBootHdr::parse_vendor_boot_image(&buffer[..]).unwrap();
+        assert_eq!(header.ramdisk_table_entries(&buffer).err(), Some(BootError::BadRamdiskTable));
+    }
+
+    #[test]
+    fn ramdisk_table_size_inconsistent() {
+        let mut buffer = [0; core::mem::size_of::<VendorBootHdrV4>()];
+        add::<VendorBootHdrV4>(
+            &mut buffer,
+            VendorBootHdrV4 {
+                vendor_ramdisk_table_entry_num: 10,
+                vendor_ramdisk_table_entry_size: size_of::<VendorRamdiskTableEntryV4>() as u32,
+                vendor_ramdisk_table_size: 1,
+                ..Default::default()
+            },
+        );
+        let header = VendorBootHdr::parse_vendor_boot_image(&buffer[..]).unwrap();
+        assert_eq!(header.ramdisk_table_entries(&buffer).err(), Some(BootError::BadRamdiskTable));
+    }
+
+    #[test]
+    fn ramdisk_table_entry_out_of_bounds() {
+        const PAGE_SIZE: usize = 4096;
+        const VENDOR_RAMDISK_SIZE: usize = 10;
+        let table_offset = round_up_to_page_size(size_of::<VendorBootHdrV4>(), PAGE_SIZE)
+            + round_up_to_page_size(VENDOR_RAMDISK_SIZE, PAGE_SIZE);
+        let table_size = size_of::<VendorRamdiskTableEntryV4>();
+        let mut buffer = vec![0u8; table_offset + table_size];
+        add::<VendorBootHdrV4>(
+            &mut buffer,
+            VendorBootHdrV4 {
+                v3_img_hdr: VendorBootHdrV3 {
+                    header_version: 4,
+                    page_size: PAGE_SIZE as u32,
+                    vendor_ramdisk_size: VENDOR_RAMDISK_SIZE as u32,
+                    ..Default::default()
+                },
+                vendor_ramdisk_table_size: table_size as u32,
+                vendor_ramdisk_table_entry_num: 1,
+                vendor_ramdisk_table_entry_size: size_of::<VendorRamdiskTableEntryV4>() as u32,
+                ..Default::default()
+            },
+        );
+        add::<VendorRamdiskTableEntryV4>(
+            &mut buffer[table_offset..],
+            VendorRamdiskTableEntryV4 {
+                ramdisk_size: 1234,
+                ramdisk_offset: 0,
+                ramdisk_type: VENDOR_RAMDISK_TYPE_NONE,
+                ramdisk_name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            },
+        );
+        let header = VendorBootHdr::parse_vendor_boot_image(&buffer[..]).unwrap();
+        assert_eq!(header.ramdisk_table_entries(&buffer).err(), Some(BootError::BadRamdiskTable));
+    }
+
+    #[test]
+    fn bootconfig_round_trips() {
+        let params = b"androidboot.foo=bar\nandroidboot.baz=qux\n";
+        let section = build_bootconfig(params);
+        assert_eq!(parse_bootconfig_trailer(&section), Ok(&params[..]));
+    }
+
+    #[test]
+    fn bootconfig_empty_params_round_trip() {
+        let section = build_bootconfig(&[]);
+        assert_eq!(parse_bootconfig_trailer(&section), Ok(&b""[..]));
+    }
+
+    #[test]
+    fn bootconfig_bad_magic() {
+        let mut section = build_bootconfig(b"androidboot.foo=bar\n");
+        let last = section.len() - 1;
+        section[last] = b'!';
+        assert_eq!(parse_bootconfig_trailer(&section), Err(BootError::BadBootconfig));
+    }
+
+    #[test]
+    fn bootconfig_bad_checksum() {
+        let mut section = build_bootconfig(b"androidboot.foo=bar\n");
+        section[0] ^= 0xFF;
+        assert_eq!(parse_bootconfig_trailer(&section), Err(BootError::BadBootconfig));
+    }
+
+    #[test]
+    fn bootconfig_buffer_too_small() {
+        assert_eq!(parse_bootconfig_trailer(&[0; 4]), Err(BootError::BadBootconfig));
+    }
+
+    #[test]
+    fn unwrap_no_wrapper() {
+        let buffer = [0u8; 64];
+        assert_eq!(unwrap_vendor_header(&buffer), (VendorWrapper::None, &buffer[..]));
+    }
+
+    #[test]
+    fn mtk_wrapper_round_trips() {
+        let inner = b"ANDROID!boot image contents";
+        let mut type_name = [0u8; MTK_TYPE_NAME_SIZE];
+        type_name[..10].copy_from_slice(b"BOOTIMAGE\0");
+        let wrapper = VendorWrapper::Mtk { content_size: inner.len() as u32, type_name };
+        let wrapped = rewrap(&wrapper, inner);
+
+        let (detected, unwrapped) = unwrap_vendor_header(&wrapped);
+        assert_eq!(detected, wrapper);
+        assert_eq!(unwrapped, inner);
+    }
+
+    #[test]
+    fn dhtb_wrapper_round_trips() {
+        let inner = b"ANDROID!boot image contents";
+        let wrapper = VendorWrapper::Dhtb {
+            payload_sha256: [0xAB; DHTB_PAYLOAD_HASH_SIZE],
+            size: inner.len() as u32,
+        };
+        let wrapped = rewrap(&wrapper, inner);
+
+        let (detected, unwrapped) = unwrap_vendor_header(&wrapped);
+        #[cfg(feature = "avb")]
+        let expected = VendorWrapper::Dhtb { payload_sha256: dhtb_sha256(inner), size: inner.len() as u32 };
+        #[cfg(not(feature = "avb"))]
+        let expected = wrapper;
+        assert_eq!(detected, expected);
+        assert_eq!(unwrapped, inner);
+    }
+
+    #[cfg(feature = "avb")]
+    fn dhtb_sha256(inner: &[u8]) -> [u8; DHTB_PAYLOAD_HASH_SIZE] {
+        use sha2::{Digest, Sha256};
+
+        let digest = Sha256::digest(inner);
+        let mut payload_sha256 = [0u8; DHTB_PAYLOAD_HASH_SIZE];
+        payload_sha256[..digest.len()].copy_from_slice(&digest);
+        payload_sha256
+    }
+
+    #[test]
+    fn dhtb_wrapper_detected_from_real_magic() {
+        // Real DHTB-wrapped images (e.g. Sony/Qualcomm devices) set the byte
+        // after "DHTB" to 0x01, not 0x00; this buffer is hand-built from that
+        // layout rather than round-tripped through `rewrap`.
+        let mut buffer = vec![0u8; VENDOR_WRAPPER_SIZE + 4];
+        buffer[0..8].copy_from_slice(b"DHTB\x01\0\0\0");
+        let payload_sha256 = [0xCDu8; DHTB_PAYLOAD_HASH_SIZE];
+        buffer[8..8 + DHTB_PAYLOAD_HASH_SIZE].copy_from_slice(&payload_sha256);
+        let size_start = 8 + DHTB_PAYLOAD_HASH_SIZE;
+        buffer[size_start..size_start + 4].copy_from_slice(&4u32.to_le_bytes());
+        buffer[VENDOR_WRAPPER_SIZE..].copy_from_slice(b"body");
+
+        let (detected, unwrapped) = unwrap_vendor_header(&buffer);
+        assert_eq!(detected, VendorWrapper::Dhtb { payload_sha256, size: 4 });
+        assert_eq!(unwrapped, b"body");
+    }
+
+    #[test]
+    fn signblob_wrapper_round_trips() {
+        let inner = b"ANDROID!boot image contents";
+        let wrapped = rewrap(&VendorWrapper::SamsungSignBlob, inner);
+
+        let (detected, unwrapped) = unwrap_vendor_header(&wrapped);
+        assert_eq!(detected, VendorWrapper::SamsungSignBlob);
+        assert_eq!(unwrapped, inner);
+    }
+
+    #[test]
+    fn zimage_is_detected_without_stripping() {
+        let mut buffer = [0u8; 64];
+        buffer[ZIMAGE_MAGIC_OFFSET..ZIMAGE_MAGIC_OFFSET + 4]
+            .copy_from_slice(&ZIMAGE_MAGIC.to_le_bytes());
+        let (detected, unwrapped) = unwrap_vendor_header(&buffer);
+        assert_eq!(detected, VendorWrapper::ZImage);
+        assert_eq!(unwrapped, &buffer[..]);
+    }
+
+    #[test]
+    fn build_and_parse_v0_round_trips() {
+        let kernel = b"kernel contents";
+        let ramdisk = b"ramdisk contents";
+        let image = BootImageBuilder::new(0)
+            .page_size(4096)
+            .kernel(kernel, 0x1000)
+            .ramdisk(ramdisk, 0x2000)
+            .build()
+            .unwrap();
+
+        let header = match BootImg::parse_boot_image(&image[..]).unwrap() {
+            BootImg::V0Hdr(hdr) => hdr,
+            other => panic!("expected V0Hdr, got {other:?}"),
+        };
+        assert_eq!({ header.kernel_size }, kernel.len() as u32);
+        assert_eq!({ header.ramdisk_size }, ramdisk.len() as u32);
+        assert_eq!(&image[4096..4096 + kernel.len()], kernel);
+    }
+
+    #[test]
+    fn build_and_parse_v3_round_trips() {
+        let kernel = vec![0x11u8; 10];
+        let ramdisk = vec![0x22u8; 20];
+        let image =
+            BootImageBuilder::new(3).kernel(&kernel, 0).ramdisk(&ramdisk, 0).build().unwrap();
+
+        let header = match BootImg::parse_boot_image(&image[..]).unwrap() {
+            BootImg::V3Hdr(hdr) => hdr,
+            other => panic!("expected V3Hdr, got {other:?}"),
+        };
+        assert_eq!({ header.kernel_size }, kernel.len() as u32);
+        assert_eq!({ header.ramdisk_size }, ramdisk.len() as u32);
+    }
+
+    #[test]
+    fn build_and_parse_v4_round_trips() {
+        let kernel = vec![0x33u8; 10];
+        let ramdisk = vec![0x44u8; 20];
+        let signature = vec![0x55u8; 8];
+        let image = BootImageBuilder::new(4)
+            .kernel(&kernel, 0)
+            .ramdisk(&ramdisk, 0)
+            .signature(&signature)
+            .build()
+            .unwrap();
+
+        let header = match BootImg::parse_boot_image(&image[..]).unwrap() {
+            BootImg::V4Hdr(hdr) => hdr,
+            other => panic!("expected V4Hdr, got {other:?}"),
+        };
+        assert_eq!({ header.v3_hdr.kernel_size }, kernel.len() as u32);
+        assert_eq!({ header.signature_size }, signature.len() as u32);
+    }
+
+    #[test]
+    fn build_unknown_version() {
+        assert_eq!(BootImageBuilder::new(2112).build().err(), Some(BootError::UnknownVersion));
+    }
+
+    #[test]
+    fn boot_signature_round_trips() {
+        let kernel = vec![0x33u8; 10];
+        let ramdisk = vec![0x44u8; 20];
+        let signature = b"this would be an AVB structure".to_vec();
+        let image = BootImageBuilder::new(4)
+            .kernel(&kernel, 0)
+            .ramdisk(&ramdisk, 0)
+            .signature(&signature)
+            .build()
+            .unwrap();
+
+        let header = BootImg::parse_boot_image(&image[..]).unwrap();
+        assert_eq!(header.boot_signature(&image).unwrap(), &signature[..]);
+    }
+
+    #[test]
+    fn boot_signature_missing() {
+        let kernel = vec![0x33u8; 10];
+        let image = BootImageBuilder::new(4).kernel(&kernel, 0).build().unwrap();
+        let header = BootImg::parse_boot_image(&image[..]).unwrap();
+        assert_eq!(header.boot_signature(&image).err(), Some(BootError::BadSignatureRegion));
+    }
+
+    #[test]
+    fn boot_signature_wrong_version() {
+        let mut buffer = [0; core::mem::size_of::<BootImgHdrV3>()];
+        add::<BootImgHdrV3>(&mut buffer, Default::default());
+        let header = BootImg::parse_boot_image(&buffer[..]).unwrap();
+        assert_eq!(header.boot_signature(&buffer).err(), Some(BootError::UnknownVersion));
+    }
+
+    #[test]
+    fn boot_layout_v0() {
+        let kernel = vec![0x11u8; 10];
+        let ramdisk = vec![0x22u8; 20];
+        let second = vec![0x33u8; 5];
+        let image = BootImageBuilder::new(0)
+            .page_size(4096)
+            .kernel(&kernel, 0)
+            .ramdisk(&ramdisk, 0)
+            .second(&second, 0)
+            .build()
+            .unwrap();
+        let header = BootImg::parse_boot_image(&image[..]).unwrap();
+        let layout = header.layout(&image).unwrap();
+        assert_eq!(layout.kernel, (4096, kernel.len()));
+        assert_eq!(layout.ramdisk, (8192, ramdisk.len()));
+        assert_eq!(layout.second, Some((12288, second.len())));
+        assert_eq!(layout.recovery_dtbo, None);
+        assert_eq!(layout.dtb, None);
+    }
+
+    #[test]
+    fn boot_layout_v4() {
+        let kernel = vec![0x11u8; 10];
+        let ramdisk = vec![0x22u8; 20];
+        let signature = vec![0x33u8; 8];
+        let image = BootImageBuilder::new(4)
+            .kernel(&kernel, 0)
+            .ramdisk(&ramdisk, 0)
+            .signature(&signature)
+            .build()
+            .unwrap();
+        let header = BootImg::parse_boot_image(&image[..]).unwrap();
+        let layout = header.layout(&image).unwrap();
+        assert_eq!(layout.kernel, (4096, kernel.len()));
+        assert_eq!(layout.ramdisk, (8192, ramdisk.len()));
+        assert_eq!(layout.signature, Some((12288, signature.len())));
+    }
+
+    #[test]
+    fn boot_layout_buffer_too_small() {
+        let mut buffer = [0; core::mem::size_of::<BootImgHdrV0>()];
+        add::<BootImgHdrV0>(
+            &mut buffer,
+            BootImgHdrV0 { page_size: 4096, kernel_size: 1, ..Default::default() },
+        );
+        let header = BootImg::parse_boot_image(&buffer[..]).unwrap();
+        assert_eq!(header.layout(&buffer).err(), Some(BootError::BufferTooSmall));
+    }
+
+    #[test]
+    fn vendor_boot_layout_v4() {
+        const PAGE_SIZE: usize = 4096;
+        let mut buffer = vec![0u8; PAGE_SIZE];
+        add::<VendorBootHdrV4>(
+            &mut buffer,
+            VendorBootHdrV4 {
+                v3_img_hdr: VendorBootHdrV3 {
+                    header_version: 4,
+                    page_size: PAGE_SIZE as u32,
+                    ..Default::default()
+                },
+                ..Default::default()
+            },
+        );
+        let header = VendorBootHdr::parse_vendor_boot_image(&buffer[..]).unwrap();
+        let layout = header.layout(&buffer).unwrap();
+        assert_eq!(layout.vendor_ramdisk, (4096, 0));
+        assert_eq!(layout.dtb, (4096, 0));
+        assert_eq!(layout.ramdisk_table, Some((4096, 0)));
+        assert_eq!(layout.bootconfig, Some((4096, 0)));
+    }
+
+    #[test]
+    fn build_and_parse_vendor_v3_round_trips() {
+        let ramdisk = vec![0x11u8; 10];
+        let dtb = vec![0x22u8; 20];
+        let image = VendorBootImageBuilder::new(3)
+            .dtb(&dtb, 0)
+            .add_ramdisk(VendorRamdiskFragment {
+                data: &ramdisk,
+                ramdisk_type: VENDOR_RAMDISK_TYPE_NONE,
+                name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            })
+            .build()
+            .unwrap();
+
+        let header = match VendorBootHdr::parse_vendor_boot_image(&image[..]).unwrap() {
+            VendorBootHdr::V3Hdr(hdr) => hdr,
+            other => panic!("expected V3Hdr, got {other:?}"),
+        };
+        assert_eq!({ header.vendor_ramdisk_size }, ramdisk.len() as u32);
+        assert_eq!({ header.dtb_size }, dtb.len() as u32);
+    }
+
+    #[test]
+    fn build_and_parse_vendor_v4_round_trips() {
+        let ramdisk_a = vec![0xAAu8; 10];
+        let ramdisk_b = vec![0xBBu8; 15];
+        let dtb = vec![0x22u8; 20];
+        let params = b"androidboot.foo=bar\n";
+        let image = VendorBootImageBuilder::new(4)
+            .dtb(&dtb, 0)
+            .add_ramdisk(VendorRamdiskFragment {
+                data: &ramdisk_a,
+                ramdisk_type: VENDOR_RAMDISK_TYPE_PLATFORM,
+                name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            })
+            .add_ramdisk(VendorRamdiskFragment {
+                data: &ramdisk_b,
+                ramdisk_type: VENDOR_RAMDISK_TYPE_RECOVERY,
+                name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            })
+            .bootconfig_params(params)
+            .build()
+            .unwrap();
+
+        let header = VendorBootHdr::parse_vendor_boot_image(&image[..]).unwrap();
+        let entries: Vec<_> = header.ramdisk_table_entries(&image).unwrap().collect();
+        assert_eq!(entries.len(), 2);
+        let (entry0_size, entry0_type) = (entries[0].ramdisk_size, entries[0].ramdisk_type);
+        let (entry1_size, entry1_offset, entry1_type) =
+            (entries[1].ramdisk_size, entries[1].ramdisk_offset, entries[1].ramdisk_type);
+        assert_eq!(entry0_size, ramdisk_a.len() as u32);
+        assert_eq!(entry0_type, VendorRamdiskType::PLATFORM);
+        assert_eq!(entry1_size, ramdisk_b.len() as u32);
+        assert_eq!(entry1_offset, ramdisk_a.len() as u32);
+        assert_eq!(entry1_type, VendorRamdiskType::RECOVERY);
+
+        let layout = header.layout(&image).unwrap();
+        let bootconfig = &image[layout.bootconfig.unwrap().0..][..layout.bootconfig.unwrap().1];
+        assert_eq!(parse_bootconfig_trailer(bootconfig), Ok(&params[..]));
+    }
+
+    #[test]
+    fn vendor_build_unknown_version() {
+        assert_eq!(
+            VendorBootImageBuilder::new(2112).build().err(),
+            Some(BootError::UnknownVersion)
+        );
+    }
+
+    fn build_vendor_v4_with_two_ramdisks() -> (Vec<u8>, Vec<u8>, Vec<u8>) {
+        let platform = vec![0xAAu8; 10];
+        let recovery = vec![0xBBu8; 15];
+        let image = VendorBootImageBuilder::new(4)
+            .add_ramdisk(VendorRamdiskFragment {
+                data: &platform,
+                ramdisk_type: VENDOR_RAMDISK_TYPE_PLATFORM,
+                name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            })
+            .add_ramdisk(VendorRamdiskFragment {
+                data: &recovery,
+                ramdisk_type: VENDOR_RAMDISK_TYPE_RECOVERY,
+                name: [0; VENDOR_RAMDISK_NAME_SIZE],
+                board_id: [0; VENDOR_RAMDISK_TABLE_ENTRY_BOARD_ID_SIZE],
+            })
+            .build()
+            .unwrap();
+        (image, platform, recovery)
+    }
+
+    #[test]
+    fn replace_vendor_ramdisk_by_type() {
+        let (image, platform, recovery) = build_vendor_v4_with_two_ramdisks();
+        let new_recovery = vec![0xCCu8; 7];
+
+        let replaced = replace_vendor_ramdisk(
+            &image,
+            RamdiskSelector::Type(VENDOR_RAMDISK_TYPE_RECOVERY),
+            &new_recovery,
+        )
+        .unwrap();
+
+        let header = VendorBootHdr::parse_vendor_boot_image(&replaced[..]).unwrap();
+        let entries: Vec<_> = header.ramdisk_table_entries(&replaced).unwrap().collect();
+        let entry0_size = entries[0].ramdisk_size;
+        let (entry1_size, entry1_offset) = (entries[1].ramdisk_size, entries[1].ramdisk_offset);
+        assert_eq!(entry0_size, platform.len() as u32);
+        assert_eq!(entry1_size, new_recovery.len() as u32);
+        assert_eq!(entry1_offset, platform.len() as u32);
+
+        let layout = header.layout(&replaced).unwrap();
+        let (start, _) = layout.vendor_ramdisk;
+        assert_eq!(&replaced[start..start + platform.len()], &platform[..]);
+        assert_eq!(
+            &replaced[start + platform.len()..start + platform.len() + new_recovery.len()],
+            &new_recovery[..]
+        );
+        let _ = recovery;
+    }
+
+    #[test]
+    fn replace_vendor_ramdisk_not_found() {
+        let (image, ..) = build_vendor_v4_with_two_ramdisks();
+        assert_eq!(
+            replace_vendor_ramdisk(&image, RamdiskSelector::Type(VENDOR_RAMDISK_TYPE_DLKM), &[])
+                .err(),
+            Some(BootError::RamdiskNotFound)
+        );
+    }
+
+    #[test]
+    fn replace_vendor_ramdisk_wrong_version() {
+        let mut buffer = [0; core::mem::size_of::<VendorBootHdrV3>()];
+        add::<VendorBootHdrV3>(&mut buffer, Default::default());
+        assert_eq!(
+            replace_vendor_ramdisk(
+                &buffer,
+                RamdiskSelector::Type(VENDOR_RAMDISK_TYPE_NONE),
+                &[]
+            )
+            .err(),
+            Some(BootError::UnknownVersion)
+        );
+    }
+
+    fn build_avb_hash_descriptor(
+        hash_algorithm: &[u8],
+        partition_name: &[u8],
+        salt: &[u8],
+        digest: &[u8],
+    ) -> Vec<u8> {
+        let mut fixed = Vec::with_capacity(AVB_HASH_DESCRIPTOR_FIXED_SIZE);
+        fixed.extend_from_slice(&0u64.to_be_bytes()); // image_size
+        let mut hash_algorithm_field = [0u8; 32];
+        hash_algorithm_field[..hash_algorithm.len()].copy_from_slice(hash_algorithm);
+        fixed.extend_from_slice(&hash_algorithm_field);
+        fixed.extend_from_slice(&(partition_name.len() as u32).to_be_bytes());
+        fixed.extend_from_slice(&(salt.len() as u32).to_be_bytes());
+        fixed.extend_from_slice(&(digest.len() as u32).to_be_bytes());
+        fixed.extend_from_slice(&0u32.to_be_bytes()); // flags
+        fixed.extend_from_slice(&[0u8; 60]); // reserved
+        assert_eq!(fixed.len(), AVB_HASH_DESCRIPTOR_FIXED_SIZE);
+
+        let mut payload = fixed;
+        payload.extend_from_slice(partition_name);
+        payload.extend_from_slice(salt);
+        payload.extend_from_slice(digest);
+
+        let mut descriptor = Vec::new();
+        descriptor.extend_from_slice(&AVB_DESCRIPTOR_TAG_HASH.to_be_bytes());
+        descriptor.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+        descriptor.extend_from_slice(&payload);
+        descriptor
+    }
+
+    fn build_vbmeta_with_hash_descriptor(descriptor: &[u8]) -> Vec<u8> {
+        let mut vbmeta = vec![0u8; AVB_VBMETA_HEADER_SIZE];
+        vbmeta[0..4].copy_from_slice(b"AVB0");
+        // authentication_data_block_size: 0
+        // descriptors_offset: 0, descriptors_size: descriptor.len()
+        vbmeta[104..112].copy_from_slice(&(descriptor.len() as u64).to_be_bytes());
+        vbmeta.extend_from_slice(descriptor);
+        vbmeta
+    }
+
+    #[test]
+    fn find_avb_footer_round_trips() {
+        let mut buffer = vec![0u8; 1024];
+        let footer_start = buffer.len() - AVB_FOOTER_SIZE;
+        buffer[footer_start..footer_start + 4].copy_from_slice(&AVB_FOOTER_MAGIC);
+        buffer[footer_start + 4..footer_start + 8].copy_from_slice(&2u32.to_be_bytes());
+        buffer[footer_start + 8..footer_start + 12].copy_from_slice(&0u32.to_be_bytes());
+        buffer[footer_start + 12..footer_start + 20].copy_from_slice(&900u64.to_be_bytes());
+        buffer[footer_start + 20..footer_start + 28].copy_from_slice(&900u64.to_be_bytes());
+        buffer[footer_start + 28..footer_start + 36].copy_from_slice(&64u64.to_be_bytes());
+
+        let footer = find_avb_footer(&buffer).unwrap();
+        assert_eq!(
+            footer,
+            AvbFooter {
+                version_major: 2,
+                version_minor: 0,
+                original_image_size: 900,
+                vbmeta_offset: 900,
+                vbmeta_size: 64,
+            }
+        );
+    }
+
+    #[test]
+    fn find_avb_footer_bad_magic() {
+        let buffer = [0u8; AVB_FOOTER_SIZE];
+        assert_eq!(find_avb_footer(&buffer).err(), Some(BootError::BadAvbFooter));
+    }
+
+    #[test]
+    fn parse_avb_hash_descriptor_round_trips() {
+        let descriptor =
+            build_avb_hash_descriptor(b"sha256", b"boot", &[0xAA; 4], &[0xBB; 32]);
+        let vbmeta = build_vbmeta_with_hash_descriptor(&descriptor);
+
+        let parsed = parse_avb_hash_descriptor(&vbmeta).unwrap();
+        assert_eq!(parsed.hash_algorithm, "sha256");
+        assert_eq!(parsed.salt, &[0xAA; 4]);
+        assert_eq!(parsed.digest, &[0xBB; 32]);
+    }
+
+    #[test]
+    fn parse_avb_hash_descriptor_bad_magic() {
+        let mut vbmeta = vec![0u8; AVB_VBMETA_HEADER_SIZE];
+        vbmeta[0..4].copy_from_slice(b"XXXX");
+        assert_eq!(parse_avb_hash_descriptor(&vbmeta).err(), Some(BootError::BadAvbFooter));
+    }
+
+    #[test]
+    fn parse_avb_hash_descriptor_no_hash_descriptor() {
+        let mut vbmeta = vec![0u8; AVB_VBMETA_HEADER_SIZE];
+        vbmeta[0..4].copy_from_slice(b"AVB0");
+        assert_eq!(parse_avb_hash_descriptor(&vbmeta).err(), Some(BootError::BadAvbFooter));
+    }
+
+    #[test]
+    #[cfg(feature = "avb")]
+    fn verify_hash_matches_computed_digest() {
+        use sha2::{Digest, Sha256};
+
+        let salt = [0xAAu8; 4];
+        let image = b"boot image contents";
+        let mut hasher = Sha256::new();
+        hasher.update(salt);
+        hasher.update(image);
+        let digest = hasher.finalize();
+
+        let descriptor = AvbHashDescriptor {
+            image_size: image.len() as u64,
+            hash_algorithm: "sha256",
+            salt: &salt,
+            digest: &digest,
+        };
+        assert_eq!(verify_hash(&descriptor, image), Ok(()));
+    }
 }